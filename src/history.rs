@@ -0,0 +1,122 @@
+use directories::ProjectDirs;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+use uuid::Uuid;
+
+const HISTORY_FILE: &str = "history.jsonl";
+
+/// Scopes a history search to the running session or to every run ever
+/// recorded, mirroring a shell history tool's `!` vs `!!` scoping.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum FilterMode {
+    Session,
+    Global,
+}
+
+impl FilterMode {
+    pub fn cycle(self) -> Self {
+        match self {
+            FilterMode::Session => FilterMode::Global,
+            FilterMode::Global => FilterMode::Session,
+        }
+    }
+}
+
+impl std::fmt::Display for FilterMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FilterMode::Session => write!(f, "session"),
+            FilterMode::Global => write!(f, "global"),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct HistoryEntry {
+    pub session_id: String,
+    pub role: String,
+    pub content: String,
+    pub timestamp: i64,
+}
+
+/// Durable, append-only log of every exchange, stored as JSON-lines under the
+/// app's `ProjectDirs` data directory so it survives across runs.
+pub struct History {
+    path: PathBuf,
+    session_id: String,
+    entries: Vec<HistoryEntry>,
+}
+
+impl History {
+    pub fn load() -> anyhow::Result<Self> {
+        let dir = ProjectDirs::from("com", "termi-talk", "rhea").unwrap();
+        fs::create_dir_all(dir.data_dir())?;
+        let path = dir.data_dir().join(HISTORY_FILE);
+
+        let entries = if path.exists() {
+            BufReader::new(File::open(&path)?)
+                .lines()
+                .map_while(Result::ok)
+                .filter_map(|line| serde_json::from_str(&line).ok())
+                .collect()
+        } else {
+            Vec::new()
+        };
+
+        Ok(Self {
+            path,
+            session_id: Uuid::new_v4().to_string(),
+            entries,
+        })
+    }
+
+    pub fn record(&mut self, role: &str, content: &str) -> anyhow::Result<()> {
+        let entry = HistoryEntry {
+            session_id: self.session_id.clone(),
+            role: role.to_string(),
+            content: content.to_string(),
+            timestamp: now_unix(),
+        };
+
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        writeln!(file, "{}", serde_json::to_string(&entry)?)?;
+
+        self.entries.push(entry);
+        Ok(())
+    }
+
+    pub fn search(&self, query: &str, filter: FilterMode) -> Vec<&HistoryEntry> {
+        self.entries
+            .iter()
+            .filter(|entry| filter == FilterMode::Global || entry.session_id == self.session_id)
+            .filter(|entry| fuzzy_match(&entry.content, query))
+            .collect()
+    }
+}
+
+/// Subsequence match: every character of `needle` must appear in `haystack`
+/// in order, letting `"cpu tmp"` match `"what's my CPU temperature?"`.
+fn fuzzy_match(haystack: &str, needle: &str) -> bool {
+    if needle.is_empty() {
+        return true;
+    }
+    let haystack = haystack.to_lowercase();
+    let mut chars = haystack.chars();
+    needle
+        .to_lowercase()
+        .chars()
+        .all(|needle_char| chars.any(|hay_char| hay_char == needle_char))
+}
+
+fn now_unix() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs() as i64
+}
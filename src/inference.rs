@@ -7,6 +7,7 @@ use mistralrs::GGUFSpecificConfig;
 use mistralrs::MistralRs;
 use mistralrs::MistralRsBuilder;
 use mistralrs::ModelDType;
+use mistralrs::Pipeline;
 use mistralrs::SchedulerConfig;
 use mistralrs::TokenSource;
 use std::fs::create_dir_all;
@@ -14,9 +15,35 @@ use std::fs::File;
 use std::io;
 use std::io::Write;
 use std::sync::Arc;
+use tokenizers::Tokenizer;
 use tokio::runtime::Runtime;
 
 const MODEL: &str = "Humanish-LLama3-8B-Instruct-Q4_K_M.gguf";
+
+/// Default context budget in tokens, used when `TERMI_TALK_TOKEN_BUDGET` is
+/// unset. CUDA users with headroom can raise it; CPU-only users on a tight
+/// KV cache can lower it.
+const DEFAULT_TOKEN_BUDGET: usize = 800;
+
+/// Reads the configured context budget from the `TERMI_TALK_TOKEN_BUDGET`
+/// environment variable, falling back to [`DEFAULT_TOKEN_BUDGET`].
+pub fn token_budget() -> usize {
+    std::env::var("TERMI_TALK_TOKEN_BUDGET")
+        .ok()
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(DEFAULT_TOKEN_BUDGET)
+}
+
+/// Counts tokens with the model's own tokenizer rather than a character
+/// heuristic, so `token_budget` trims by what the model actually sees,
+/// non-ASCII text and code included.
+pub fn count_tokens(tokenizer: &Tokenizer, text: &str) -> usize {
+    tokenizer
+        .encode(text, false)
+        .map(|encoding| encoding.get_ids().len())
+        .unwrap_or(0)
+}
+
 pub const SYSTEM_PROMPT: &str = r#"
 You are *The Quirky Scientist*—a warm, highly knowledgeable AI with a playful edge and a passion for science. You're a bit nerdy, love quirky facts, and explain complex ideas with fun analogies. Your tone is friendly, upbeat, and slightly whimsical, making even challenging topics feel accessible. Answer should be short, with a minimal number of words and witty."#;
 async fn download_model() -> anyhow::Result<()> {
@@ -54,7 +81,7 @@ async fn download_model() -> anyhow::Result<()> {
     Ok(())
 }
 
-pub fn load_model() -> anyhow::Result<Arc<MistralRs>> {
+pub fn load_model() -> anyhow::Result<(Arc<MistralRs>, Arc<Tokenizer>)> {
     let dir = ProjectDirs::from("com", "termi-talk", "rhea").unwrap();
     let path = dir.data_dir().join(MODEL);
     if !path.exists() {
@@ -87,13 +114,19 @@ pub fn load_model() -> anyhow::Result<Arc<MistralRs>> {
         None,
     )?;
 
-    Ok(MistralRsBuilder::new(
-        pipeline,
-        SchedulerConfig::DefaultScheduler {
-            method: DefaultSchedulerMethod::Fixed(5.try_into().unwrap()),
-        },
-    )
-    .build())
+    let rt = Runtime::new().unwrap();
+    let tokenizer = rt.block_on(async { pipeline.lock().await.tokenizer() });
+
+    Ok((
+        MistralRsBuilder::new(
+            pipeline,
+            SchedulerConfig::DefaultScheduler {
+                method: DefaultSchedulerMethod::Fixed(5.try_into().unwrap()),
+            },
+        )
+        .build(),
+        tokenizer,
+    ))
 }
 
 pub fn wrap_text(text: String, max_width: usize) -> Vec<String> {
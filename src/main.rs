@@ -5,7 +5,7 @@ use mistralrs::{
     MistralRs, NormalRequest, Request, RequestMessage, ResponseOk, Result, SamplingParams,
 };
 use ratatui::{
-    crossterm::event::{self, Event, KeyCode, KeyEventKind},
+    crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
     layout::{Constraint, Layout, Position},
     style::{Color, Modifier, Style, Stylize},
     text::{Line, Span, Text},
@@ -14,18 +14,40 @@ use ratatui::{
 };
 use std::fmt;
 use std::sync::Arc;
-use tokio::sync::mpsc::channel;
+use std::time::Duration;
+use tokenizers::Tokenizer;
+use tokio::sync::mpsc::{channel, Sender};
 
+mod history;
 mod inference;
+mod revisions;
+use history::{FilterMode, History};
 use inference::*;
+use revisions::RevisionTree;
+
+const TICK_RATE: Duration = Duration::from_millis(100);
 
 fn main() -> Result<()> {
     let terminal = ratatui::init();
-    let app_result = App::new().run(terminal);
+    let app = App::new();
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let app_result = runtime.block_on(app.run(terminal));
     ratatui::restore();
     app_result
 }
 
+/// Everything the main loop can react to, funneled through one channel so
+/// typing, periodic ticks and streamed inference output interleave
+/// instead of taking turns blocking each other. A `Tick` only triggers a
+/// redraw; nothing is animated on it.
+enum AppEvent {
+    Key(KeyEvent),
+    Tick,
+    InferenceDelta(u64, String),
+    InferenceDone(u64),
+    Resize,
+}
+
 struct App {
     input: String,
     character_index: usize,
@@ -33,8 +55,28 @@ struct App {
     messages: Vec<(Who, String)>,
     messages_state: ListState,
     model: Arc<MistralRs>,
+    tokenizer: Arc<Tokenizer>,
     context: Vec<IndexMap<String, MessageContent>>,
-    context_len: usize,
+    context_ids: Vec<u64>,
+    context_token_counts: Vec<usize>,
+    next_context_id: u64,
+    context_tokens: usize,
+    token_budget: usize,
+    context_pinned: Vec<bool>,
+    attached_file: Option<String>,
+    attached_file_id: Option<u64>,
+    generation_task: Option<tokio::task::JoinHandle<()>>,
+    generation_epoch: u64,
+    history: History,
+    filter_mode: FilterMode,
+    search_query: String,
+    search_selected: usize,
+    scroll_offset: usize,
+    stick_to_bottom: bool,
+    last_line_index: usize,
+    revisions: RevisionTree,
+    pending_messages_start: usize,
+    pending_context_ids: Vec<u64>,
 }
 
 #[derive(PartialEq)]
@@ -42,10 +84,11 @@ enum InputMode {
     Normal,
     Editing,
     Generating,
+    Searching,
 }
 
 #[derive(Clone)]
-enum Who {
+pub(crate) enum Who {
     Me,
     Assistant,
     Empty,
@@ -63,22 +106,116 @@ impl fmt::Display for Who {
 
 impl App {
     fn new() -> Self {
-        Self {
+        let (model, tokenizer) = load_model().unwrap();
+        let mut app = Self {
             input: String::new(),
             input_mode: InputMode::Editing,
             messages: Vec::new(),
             messages_state: ListState::default(),
             character_index: 0,
-            model: load_model().unwrap(),
-            context: vec![IndexMap::from([
+            model,
+            tokenizer,
+            context: Vec::new(),
+            context_ids: Vec::new(),
+            context_token_counts: Vec::new(),
+            next_context_id: 0,
+            context_tokens: 0,
+            token_budget: token_budget(),
+            context_pinned: Vec::new(),
+            attached_file: None,
+            attached_file_id: None,
+            generation_task: None,
+            generation_epoch: 0,
+            history: History::load().unwrap(),
+            filter_mode: FilterMode::Session,
+            search_query: String::new(),
+            search_selected: 0,
+            scroll_offset: 0,
+            stick_to_bottom: true,
+            last_line_index: 0,
+            revisions: RevisionTree::new(),
+            pending_messages_start: 0,
+            pending_context_ids: Vec::new(),
+        };
+        let system_tokens = count_tokens(&app.tokenizer, SYSTEM_PROMPT);
+        app.push_context(
+            IndexMap::from([
                 ("role".to_string(), Either::Left("system".to_string())),
                 (
                     "content".to_string(),
                     Either::Left(SYSTEM_PROMPT.to_string()),
                 ),
-            ])],
-            context_len: SYSTEM_PROMPT.len(),
-        }
+            ]),
+            true,
+            system_tokens,
+        );
+        app
+    }
+
+    /// Appends a new, freshly identified entry to `context` and keeps
+    /// `context_ids`/`context_token_counts`/`context_pinned`/`context_tokens`
+    /// in lockstep with it, so later removal-by-id (`remove_context_id`) can
+    /// find exactly this entry regardless of what gets appended or evicted
+    /// around it.
+    fn push_context(
+        &mut self,
+        entry: IndexMap<String, MessageContent>,
+        pinned: bool,
+        tokens: usize,
+    ) -> u64 {
+        let id = self.next_context_id;
+        self.next_context_id += 1;
+        self.insert_context(id, entry, pinned, tokens);
+        id
+    }
+
+    /// Restores a previously assigned `id` (used by `redo`, where the id
+    /// must match the one `undo` will later look for) alongside its entry,
+    /// pin state and token count.
+    fn insert_context(
+        &mut self,
+        id: u64,
+        entry: IndexMap<String, MessageContent>,
+        pinned: bool,
+        tokens: usize,
+    ) {
+        self.context.push(entry);
+        self.context_ids.push(id);
+        self.context_token_counts.push(tokens);
+        self.context_pinned.push(pinned);
+        self.context_tokens += tokens;
+    }
+
+    /// Removes the context entry tagged `id`, if it's still present —
+    /// already-evicted ids are a no-op, since the eviction loop already
+    /// accounted for their tokens.
+    fn remove_context_id(&mut self, id: u64) -> Option<IndexMap<String, MessageContent>> {
+        let idx = self.context_ids.iter().position(|existing| *existing == id)?;
+        Some(self.remove_context_at(idx))
+    }
+
+    fn remove_context_at(&mut self, idx: usize) -> IndexMap<String, MessageContent> {
+        self.context_ids.remove(idx);
+        self.context_pinned.remove(idx);
+        self.context_tokens -= self.context_token_counts.remove(idx);
+        self.context.remove(idx)
+    }
+
+    /// Snapshots the still-present entries for `ids`, in `ids` order, along
+    /// with their token counts — called right after committing a turn, while
+    /// its entries are still guaranteed present, so the revision can later
+    /// restore them on `redo` without recomputing anything.
+    fn context_snapshot(&self, ids: &[u64]) -> (Vec<IndexMap<String, MessageContent>>, Vec<usize>) {
+        ids.iter()
+            .map(|id| {
+                let idx = self
+                    .context_ids
+                    .iter()
+                    .position(|existing| existing == id)
+                    .unwrap();
+                (self.context[idx].clone(), self.context_token_counts[idx])
+            })
+            .unzip()
     }
 
     fn move_cursor_left(&mut self) {
@@ -127,14 +264,97 @@ impl App {
         self.character_index = 0;
     }
 
-    fn submit_message(&mut self) {
+    fn enter_search(&mut self) {
+        self.search_query.clear();
+        self.search_selected = 0;
+        self.input_mode = InputMode::Searching;
+    }
+
+    fn exit_search(&mut self) {
+        self.search_query.clear();
+        self.input_mode = InputMode::Normal;
+    }
+
+    /// Pulls the currently highlighted history entry back into the input box
+    /// so the user can resend or tweak it.
+    fn recall_search_selection(&mut self) {
+        if let Some(entry) = self
+            .history
+            .search(&self.search_query, self.filter_mode)
+            .get(self.search_selected)
+        {
+            self.input = entry.content.clone();
+            self.character_index = self.input.chars().count();
+        }
+        self.exit_search();
+        self.input_mode = InputMode::Editing;
+    }
+
+    /// Removes the previously attached file's pinned context entry, if any,
+    /// identified by the id stashed in `attached_file_id` rather than by
+    /// matching its content — a user message that happens to start with the
+    /// same text as an attachment header must never be mistaken for one.
+    /// `:file` replacing rather than stacking ambient context also keeps the
+    /// budget eviction loop in `finish_generation` from facing unboundedly
+    /// many permanently pinned entries it can never remove.
+    fn detach_file(&mut self) {
+        if let Some(id) = self.attached_file_id.take() {
+            self.remove_context_id(id);
+        }
+        self.attached_file = None;
+    }
+
+    /// Reads `path` and injects its contents into `context` as a pinned
+    /// system message, replacing any previously attached file, so the model
+    /// has it as ambient context for the next turn without it ever being
+    /// evicted by the truncation loop.
+    fn attach_file(&mut self, path: &str) {
+        match std::fs::read_to_string(path) {
+            Ok(contents) => {
+                self.detach_file();
+                let content = format!("Attached file `{path}`:\n{contents}");
+                let tokens = count_tokens(&self.tokenizer, &content);
+                let id = self.push_context(
+                    IndexMap::from([
+                        ("role".to_string(), Either::Left("system".to_string())),
+                        ("content".to_string(), Either::Left(content)),
+                    ]),
+                    true,
+                    tokens,
+                );
+                self.attached_file = Some(path.to_string());
+                self.attached_file_id = Some(id);
+            }
+            Err(err) => {
+                self.messages
+                    .push((Who::Assistant, format!("Could not read {path}: {err}")));
+            }
+        }
+    }
+
+    /// Sends the current input to the model and spawns a task that forwards
+    /// the streamed response onto `events` as `InferenceDelta`/`InferenceDone`,
+    /// so the main loop never blocks on inference.
+    fn submit_message(&mut self, events: Sender<AppEvent>) {
+        self.generation_epoch += 1;
+        let epoch = self.generation_epoch;
+        self.pending_messages_start = self.messages.len();
+        self.pending_context_ids.clear();
+
         self.messages.push((Who::Me, self.input.clone()));
 
-        self.context.push(IndexMap::from([
-            ("role".to_string(), Either::Left("user".to_string())),
-            ("content".to_string(), Either::Left(self.input.clone())),
-        ]));
-        self.context_len += self.input.len();
+        let tokens = count_tokens(&self.tokenizer, &self.input);
+        let id = self.push_context(
+            IndexMap::from([
+                ("role".to_string(), Either::Left("user".to_string())),
+                ("content".to_string(), Either::Left(self.input.clone())),
+            ]),
+            false,
+            tokens,
+        );
+        self.pending_context_ids.push(id);
+        let _ = self.history.record("user", &self.input);
+        self.messages.push((Who::Assistant, String::new()));
 
         let (tx, mut rx) = channel(10_000);
         let request = Request::Normal(NormalRequest {
@@ -147,7 +367,7 @@ impl App {
             },
             response: tx,
             return_logprobs: false,
-            is_streaming: false,
+            is_streaming: true,
             id: 0,
             constraint: mistralrs::Constraint::None,
             suffix: None,
@@ -162,80 +382,259 @@ impl App {
             .blocking_send(request)
             .unwrap();
 
-        let response = rx.blocking_recv().unwrap().as_result().unwrap();
-        if let ResponseOk::Done(c) = response {
-            self.messages.push((
-                Who::Assistant,
-                c.choices[0].message.content.as_ref().unwrap().to_string(),
-            ));
-            self.context.push(IndexMap::from([
+        let task = tokio::spawn(async move {
+            while let Some(response) = rx.recv().await {
+                match response.as_result().unwrap() {
+                    ResponseOk::Chunk(chunk) => {
+                        let choice = &chunk.choices[0];
+                        if let Some(delta) = choice.delta.content.clone() {
+                            if events
+                                .send(AppEvent::InferenceDelta(epoch, delta))
+                                .await
+                                .is_err()
+                            {
+                                return;
+                            }
+                        }
+                        if choice.finish_reason.is_some() {
+                            break;
+                        }
+                    }
+                    ResponseOk::Done(c) => {
+                        if let Some(content) = c.choices[0].message.content.clone() {
+                            let _ = events.send(AppEvent::InferenceDelta(epoch, content)).await;
+                        }
+                        break;
+                    }
+                    _ => {}
+                }
+            }
+            let _ = events.send(AppEvent::InferenceDone(epoch)).await;
+        });
+        self.generation_task = Some(task);
+
+        self.input.clear();
+        self.reset_cursor();
+    }
+
+    /// Aborts the in-flight generation task and finalizes whatever partial
+    /// text was streamed so far as the assistant turn, so a cancelled
+    /// generation still leaves a coherent conversation behind. Bumping
+    /// `generation_epoch` here, before `finish_generation` runs, makes sure
+    /// any `InferenceDelta`/`InferenceDone` the aborted task already queued
+    /// is recognized as stale and ignored instead of finishing the turn a
+    /// second time.
+    fn cancel_generation(&mut self) {
+        if let Some(task) = self.generation_task.take() {
+            task.abort();
+        }
+        self.generation_epoch += 1;
+        self.finish_generation();
+        self.input_mode = InputMode::Editing;
+    }
+
+    /// Commits the fully streamed assistant reply into `context` and enforces
+    /// `token_budget` by dropping whole oldest, unpinned turns.
+    fn finish_generation(&mut self) {
+        self.generation_task = None;
+
+        let reply = self
+            .messages
+            .last()
+            .map(|(_, text)| text.clone())
+            .unwrap_or_default();
+        let _ = self.history.record("assistant", &reply);
+        let reply_tokens = count_tokens(&self.tokenizer, &reply);
+        let id = self.push_context(
+            IndexMap::from([
                 ("role".to_string(), Either::Left("assistant".to_string())),
-                (
-                    "content".to_string(),
-                    Either::Left(
-                        c.choices[0]
-                            .message
-                            .content
-                            .as_ref()
-                            .unwrap()
-                            .chars()
-                            .filter(|c| c.is_alphanumeric())
-                            .collect(),
-                    ),
-                ),
-            ]));
-            self.context_len += c.choices[0]
-                .message
-                .content
-                .as_ref()
-                .unwrap()
-                .chars()
-                .filter(|c| c.is_alphanumeric())
-                .collect::<String>()
-                .len();
+                ("content".to_string(), Either::Left(reply.clone())),
+            ]),
+            false,
+            reply_tokens,
+        );
+        self.pending_context_ids.push(id);
+
+        let (context_snapshot, context_tokens_snapshot) =
+            self.context_snapshot(&self.pending_context_ids);
+        self.revisions.commit(
+            self.messages[self.pending_messages_start..].to_vec(),
+            context_snapshot,
+            self.pending_context_ids.clone(),
+            context_tokens_snapshot,
+        );
+
+        let is_user = |entry: &IndexMap<String, MessageContent>| {
+            matches!(entry.get("role"), Some(Either::Left(role)) if role == "user")
+        };
+        while self.context_tokens > self.token_budget {
+            let Some(idx) = self.context_pinned.iter().position(|pinned| !pinned) else {
+                break;
+            };
+            self.remove_context_at(idx);
+            // Drop the rest of that turn too, up to the next user message.
+            while idx < self.context.len()
+                && !self.context_pinned[idx]
+                && !is_user(&self.context[idx])
+            {
+                self.remove_context_at(idx);
+            }
         }
+    }
+
+    /// Rolls back the last exchange: removes its user/assistant entries from
+    /// `messages`, and removes its own tagged entries from `context` by id
+    /// rather than by trailing length — the budget eviction loop or an
+    /// intervening `:file` attachment can both leave a revision's entries
+    /// anywhere in `context`, or already gone, by the time it's undone.
+    /// Moves the revision tree's `current` pointer to the parent turn.
+    fn undo(&mut self) {
+        let Some(revision) = self.revisions.undo() else {
+            return;
+        };
+        let messages_removed = revision.messages.len();
+        self.messages
+            .truncate(self.messages.len() - messages_removed);
 
-        while self.context_len > 1000 {
-            let question = self.context.remove(1);
-            let answer = self.context.remove(1);
-            self.context_len -= question
-                .get("content")
-                .unwrap()
-                .clone()
-                .left()
-                .unwrap()
-                .len()
-                + answer.get("content").unwrap().clone().left().unwrap().len();
+        for id in revision.context_ids.clone() {
+            self.remove_context_id(id);
         }
+    }
 
-        self.input.clear();
-        self.reset_cursor();
+    /// Reapplies the current revision's active child turn, the inverse of
+    /// [`App::undo`]. Restores each context entry under its original id so a
+    /// later `undo` can still find it regardless of what else has since been
+    /// appended or evicted.
+    fn redo(&mut self) {
+        let Some(revision) = self.revisions.redo() else {
+            return;
+        };
+        self.messages.extend(revision.messages.clone());
+
+        let entries = revision.context.clone();
+        let ids = revision.context_ids.clone();
+        let tokens = revision.context_tokens.clone();
+        for ((entry, id), tokens) in entries.into_iter().zip(ids).zip(tokens) {
+            self.insert_context(id, entry, false, tokens);
+        }
     }
 
-    fn run(mut self, mut terminal: DefaultTerminal) -> Result<()> {
-        loop {
-            terminal.draw(|frame| self.draw(frame))?;
+    async fn run(mut self, mut terminal: DefaultTerminal) -> Result<()> {
+        let (event_tx, mut event_rx) = channel(100);
 
-            if self.input_mode == InputMode::Generating {
-                self.submit_message();
-                self.input_mode = InputMode::Editing;
-                terminal.draw(|frame| self.draw(frame))?;
+        let input_tx = event_tx.clone();
+        std::thread::spawn(move || loop {
+            let Ok(ev) = event::read() else {
+                break;
+            };
+            let app_event = match ev {
+                Event::Key(key) => AppEvent::Key(key),
+                Event::Resize(_, _) => AppEvent::Resize,
+                _ => continue,
+            };
+            if input_tx.blocking_send(app_event).is_err() {
+                break;
             }
+        });
 
-            if let Event::Key(key) = event::read()? {
-                match self.input_mode {
+        let tick_tx = event_tx.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(TICK_RATE);
+            loop {
+                interval.tick().await;
+                if tick_tx.send(AppEvent::Tick).await.is_err() {
+                    break;
+                }
+            }
+        });
+
+        terminal.draw(|frame| self.draw(frame))?;
+
+        while let Some(app_event) = event_rx.recv().await {
+            match app_event {
+                AppEvent::Tick | AppEvent::Resize => {}
+                AppEvent::InferenceDelta(epoch, delta) => {
+                    if epoch == self.generation_epoch {
+                        if let Some(last) = self.messages.last_mut() {
+                            last.1.push_str(&delta);
+                        }
+                    }
+                }
+                AppEvent::InferenceDone(epoch) => {
+                    if epoch == self.generation_epoch {
+                        self.finish_generation();
+                        self.input_mode = InputMode::Editing;
+                    }
+                }
+                AppEvent::Key(key) => match self.input_mode {
                     InputMode::Normal => match key.code {
                         KeyCode::Char('e') => {
                             self.input_mode = InputMode::Editing;
                         }
+                        KeyCode::Char('/') => {
+                            self.enter_search();
+                        }
                         KeyCode::Char('q') => {
                             return Ok(());
                         }
+                        KeyCode::Up => {
+                            if self.stick_to_bottom {
+                                self.scroll_offset = self.last_line_index;
+                            }
+                            self.stick_to_bottom = false;
+                            self.scroll_offset = self.scroll_offset.saturating_sub(1);
+                        }
+                        KeyCode::Down => {
+                            self.scroll_offset = self.scroll_offset.saturating_add(1);
+                        }
+                        KeyCode::PageUp => {
+                            if self.stick_to_bottom {
+                                self.scroll_offset = self.last_line_index;
+                            }
+                            self.stick_to_bottom = false;
+                            self.scroll_offset = self.scroll_offset.saturating_sub(10);
+                        }
+                        KeyCode::PageDown => {
+                            self.scroll_offset = self.scroll_offset.saturating_add(10);
+                        }
+                        KeyCode::Char('z') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                            self.undo();
+                        }
+                        KeyCode::Char('y') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                            self.redo();
+                        }
+                        KeyCode::Left if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                            self.revisions.cycle_branch(false);
+                        }
+                        KeyCode::Right if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                            self.revisions.cycle_branch(true);
+                        }
                         _ => {}
                     },
                     InputMode::Editing if key.kind == KeyEventKind::Press => match key.code {
+                        KeyCode::Char('z') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                            self.undo();
+                        }
+                        KeyCode::Char('y') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                            self.redo();
+                        }
+                        KeyCode::Left if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                            self.revisions.cycle_branch(false);
+                        }
+                        KeyCode::Right if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                            self.revisions.cycle_branch(true);
+                        }
                         KeyCode::Enter => {
-                            self.input_mode = InputMode::Generating;
+                            if let Some(path) =
+                                self.input.strip_prefix(":file ").map(str::to_string)
+                            {
+                                self.attach_file(&path);
+                                self.input.clear();
+                                self.reset_cursor();
+                            } else {
+                                self.input_mode = InputMode::Generating;
+                                self.submit_message(event_tx.clone());
+                            }
                         }
                         KeyCode::Char(to_insert) => self.enter_char(to_insert),
                         KeyCode::Backspace => self.delete_char(),
@@ -245,10 +644,44 @@ impl App {
                         _ => {}
                     },
                     InputMode::Editing => {}
-                    InputMode::Generating => {}
-                }
+                    InputMode::Generating => match key.code {
+                        KeyCode::Esc => self.cancel_generation(),
+                        KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                            self.cancel_generation();
+                        }
+                        _ => {}
+                    },
+                    InputMode::Searching if key.kind == KeyEventKind::Press => match key.code {
+                        KeyCode::Esc => self.exit_search(),
+                        KeyCode::Enter => self.recall_search_selection(),
+                        KeyCode::Tab => {
+                            self.filter_mode = self.filter_mode.cycle();
+                            self.search_selected = 0;
+                        }
+                        KeyCode::Up => {
+                            self.search_selected = self.search_selected.saturating_sub(1);
+                        }
+                        KeyCode::Down => {
+                            self.search_selected = self.search_selected.saturating_add(1);
+                        }
+                        KeyCode::Char(to_insert) => {
+                            self.search_query.push(to_insert);
+                            self.search_selected = 0;
+                        }
+                        KeyCode::Backspace => {
+                            self.search_query.pop();
+                            self.search_selected = 0;
+                        }
+                        _ => {}
+                    },
+                    InputMode::Searching => {}
+                },
             }
+
+            terminal.draw(|frame| self.draw(frame))?;
         }
+
+        Ok(())
     }
 
     fn draw(&mut self, frame: &mut Frame) {
@@ -270,32 +703,58 @@ impl App {
                 ],
                 Style::default().add_modifier(Modifier::RAPID_BLINK),
             ),
-            InputMode::Editing => (
-                vec![
+            InputMode::Editing => {
+                let mut spans = vec![
                     "Press ".into(),
                     "Esc".bold(),
                     " to stop editing, ".into(),
                     "Enter".bold(),
                     " to record the message".into(),
-                ],
-                Style::default(),
-            ),
+                ];
+                if let Some(path) = &self.attached_file {
+                    spans.push(format!(", attached: {path}").into());
+                }
+                (spans, Style::default())
+            }
             InputMode::Generating => (
                 vec!["I'm thinking".bold(), " WAIT".bold()],
                 Style::default().fg(Color::Red),
             ),
+            InputMode::Searching => (
+                vec![
+                    "Searching ".into(),
+                    format!("({})", self.filter_mode).bold(),
+                    ", ".into(),
+                    "Tab".bold(),
+                    " to switch scope, ".into(),
+                    "Enter".bold(),
+                    " to recall, ".into(),
+                    "Esc".bold(),
+                    " to cancel".into(),
+                ],
+                Style::default(),
+            ),
         };
         let text = Text::from(Line::from(msg)).patch_style(style);
         let help_message = Paragraph::new(text);
         frame.render_widget(help_message, help_area);
 
-        let input = Paragraph::new(self.input.as_str())
+        let input_text = match self.input_mode {
+            InputMode::Searching => self.search_query.as_str(),
+            _ => self.input.as_str(),
+        };
+        let input_title = match self.input_mode {
+            InputMode::Searching => "Search",
+            _ => "Input",
+        };
+        let input = Paragraph::new(input_text)
             .style(match self.input_mode {
                 InputMode::Normal => Style::default(),
                 InputMode::Editing => Style::default().fg(Color::Yellow),
                 InputMode::Generating => Style::default(),
+                InputMode::Searching => Style::default().fg(Color::Yellow),
             })
-            .block(Block::bordered().title("Input"));
+            .block(Block::bordered().title(input_title));
         frame.render_widget(input, input_area);
         match self.input_mode {
             // Hide the cursor. `Frame` does this by default, so we don't need to do anything here
@@ -312,6 +771,35 @@ impl App {
                 // Move one line down, from the border to the input line
                 input_area.y + 1,
             )),
+
+            #[allow(clippy::cast_possible_truncation)]
+            InputMode::Searching => frame.set_cursor_position(Position::new(
+                input_area.x + self.search_query.chars().count() as u16 + 1,
+                input_area.y + 1,
+            )),
+        }
+
+        if self.input_mode == InputMode::Searching {
+            let results = self.history.search(&self.search_query, self.filter_mode);
+            let items: Vec<ListItem> = results
+                .iter()
+                .enumerate()
+                .map(|(i, entry)| {
+                    let content = Line::from(Span::raw(format!(
+                        "[{}] {}",
+                        entry.role, entry.content
+                    )));
+                    let item = ListItem::new(content);
+                    if i == self.search_selected {
+                        item.reversed()
+                    } else {
+                        item
+                    }
+                })
+                .collect();
+            let list = List::new(items).block(Block::bordered().title("History"));
+            frame.render_widget(list, messages_area);
+            return;
         }
 
         let messages: Vec<ListItem> = self
@@ -331,8 +819,19 @@ impl App {
             })
             .collect();
 
+        let last_index = messages.len().saturating_sub(1);
+        self.last_line_index = last_index;
         let messages = List::new(messages).block(Block::bordered().title("Messages"));
-        self.messages_state.select_last();
+
+        if self.stick_to_bottom {
+            self.messages_state.select_last();
+        } else {
+            self.scroll_offset = self.scroll_offset.min(last_index);
+            if self.scroll_offset == last_index {
+                self.stick_to_bottom = true;
+            }
+            self.messages_state.select(Some(self.scroll_offset));
+        }
         frame.render_stateful_widget(messages, messages_area, &mut self.messages_state);
     }
 }
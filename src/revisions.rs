@@ -0,0 +1,128 @@
+use crate::Who;
+use indexmap::IndexMap;
+use mistralrs::MessageContent;
+
+/// One turn in the conversation: the entries it appended to `messages` and
+/// `context`, plus the tree links needed to undo/redo it. `context_ids` tags
+/// each `context` entry with the id `App` assigned it, so `undo`/`redo` can
+/// find (or restore) exactly these entries by id instead of assuming they
+/// still sit at a fixed offset from the end of `context` — eviction or an
+/// intervening `:file` attachment can both move or remove what's around
+/// them. `context_tokens` mirrors `context` entry-for-entry so token
+/// accounting stays exact across eviction.
+pub struct Revision {
+    pub parent: Option<usize>,
+    pub children: Vec<usize>,
+    pub active_child: usize,
+    pub messages: Vec<(Who, String)>,
+    pub context: Vec<IndexMap<String, MessageContent>>,
+    pub context_ids: Vec<u64>,
+    pub context_tokens: Vec<usize>,
+}
+
+/// Tracks the conversation as a tree of revisions rather than a single undo
+/// stack: undoing and then asking something different branches off the
+/// current revision instead of discarding the abandoned turn, so it stays
+/// reachable via `cycle_branch` + `redo`.
+pub struct RevisionTree {
+    revisions: Vec<Revision>,
+    roots: Vec<usize>,
+    active_root: usize,
+    current: Option<usize>,
+}
+
+impl RevisionTree {
+    pub fn new() -> Self {
+        Self {
+            revisions: Vec::new(),
+            roots: Vec::new(),
+            active_root: 0,
+            current: None,
+        }
+    }
+
+    /// Records a newly completed turn as a child of the current revision
+    /// (or as a new root, if there is none yet) and makes it current.
+    pub fn commit(
+        &mut self,
+        messages: Vec<(Who, String)>,
+        context: Vec<IndexMap<String, MessageContent>>,
+        context_ids: Vec<u64>,
+        context_tokens: Vec<usize>,
+    ) {
+        let parent = self.current;
+        let id = self.revisions.len();
+        self.revisions.push(Revision {
+            parent,
+            children: Vec::new(),
+            active_child: 0,
+            messages,
+            context,
+            context_ids,
+            context_tokens,
+        });
+
+        match parent {
+            Some(parent_id) => {
+                let parent_rev = &mut self.revisions[parent_id];
+                parent_rev.children.push(id);
+                parent_rev.active_child = parent_rev.children.len() - 1;
+            }
+            None => {
+                self.roots.push(id);
+                self.active_root = self.roots.len() - 1;
+            }
+        }
+
+        self.current = Some(id);
+    }
+
+    /// Moves `current` to its parent and returns the turn to roll back.
+    pub fn undo(&mut self) -> Option<&Revision> {
+        let id = self.current?;
+        self.current = self.revisions[id].parent;
+        Some(&self.revisions[id])
+    }
+
+    /// Moves `current` onto its active child and returns the turn to
+    /// reapply.
+    pub fn redo(&mut self) -> Option<&Revision> {
+        let child = match self.current {
+            Some(id) => {
+                let revision = &self.revisions[id];
+                *revision.children.get(revision.active_child)?
+            }
+            None => *self.roots.get(self.active_root)?,
+        };
+        self.current = Some(child);
+        Some(&self.revisions[child])
+    }
+
+    /// Cycles which sibling branch `redo` will reapply, without touching
+    /// `current` — lets the user browse alternate branches an earlier undo
+    /// left behind before deciding which one to step back into.
+    pub fn cycle_branch(&mut self, forward: bool) {
+        match self.current {
+            Some(id) => {
+                let revision = &mut self.revisions[id];
+                if !revision.children.is_empty() {
+                    revision.active_child =
+                        cycle_index(revision.active_child, revision.children.len(), forward);
+                }
+            }
+            None => {
+                if !self.roots.is_empty() {
+                    self.active_root = cycle_index(self.active_root, self.roots.len(), forward);
+                }
+            }
+        }
+    }
+}
+
+fn cycle_index(index: usize, len: usize, forward: bool) -> usize {
+    if forward {
+        (index + 1) % len
+    } else {
+        (index + len - 1) % len
+    }
+}